@@ -0,0 +1,103 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! http://dom.spec.whatwg.org/#interface-abortsignal
+//!
+//! Only the piece `EventTarget::AddEventListener`'s `signal` option needs is
+//! implemented: recording "remove this listener" as an abort algorithm, and
+//! running every recorded algorithm once the signal aborts.
+
+use dom::bindings::js::{JS, JSRef, RootCollection, OptionalRootable};
+use dom::bindings::utils::{Reflector, Reflectable};
+use dom::eventtarget::{EventTarget, EventListenerEntry, EventTargetHelpers};
+use servo_util::str::DOMString;
+use std::cell::{Cell, RefCell};
+
+/// http://dom.spec.whatwg.org/#add-an-event-listener, step 7's abort
+/// algorithm, specialized to the one kind `AddEventListener` ever adds:
+/// removing the listener it just registered.
+struct AbortAlgorithm {
+    target: JS<EventTarget>,
+    type_: DOMString,
+    entry: EventListenerEntry,
+}
+
+pub struct AbortSignal {
+    pub reflector_: Reflector,
+    aborted: Cell<bool>,
+    abort_algorithms: RefCell<Vec<AbortAlgorithm>>,
+}
+
+impl AbortSignal {
+    pub fn new_inherited() -> AbortSignal {
+        AbortSignal {
+            reflector_: Reflector::new(),
+            aborted: Cell::new(false),
+            abort_algorithms: RefCell::new(vec!()),
+        }
+    }
+}
+
+impl Reflectable for AbortSignal {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        &self.reflector_
+    }
+
+    fn mut_reflector<'a>(&'a mut self) -> &'a mut Reflector {
+        &mut self.reflector_
+    }
+}
+
+pub trait AbortSignalHelpers {
+    // http://dom.spec.whatwg.org/#dom-abortsignal-aborted
+    fn Aborted(&self) -> bool;
+
+    /// Record `entry` for removal from `target`'s `type_` listener list if
+    /// and when this signal aborts. Runs `entry`'s removal immediately,
+    /// rather than recording it, if the signal is already aborted --
+    /// callers that need the already-aborted case handled before the
+    /// listener is even added (http://dom.spec.whatwg.org/#add-an-event-listener,
+    /// step 3) check `Aborted()` themselves beforehand instead of relying on this.
+    fn add_abort_algorithm(&self, target: JSRef<EventTarget>, type_: DOMString, entry: EventListenerEntry);
+
+    /// http://dom.spec.whatwg.org/#abortsignal-signal-abort
+    /// Mark this signal as aborted, then run (and forget) every recorded
+    /// abort algorithm.
+    fn signal_abort(&self);
+}
+
+impl<'a> AbortSignalHelpers for JSRef<'a, AbortSignal> {
+    fn Aborted(&self) -> bool {
+        self.aborted.get()
+    }
+
+    fn add_abort_algorithm(&self, target: JSRef<EventTarget>, type_: DOMString, entry: EventListenerEntry) {
+        if self.aborted.get() {
+            target.remove_listener(type_, &entry);
+            return;
+        }
+        self.abort_algorithms.borrow_mut().push(AbortAlgorithm {
+            target: JS::from_rooted(&target),
+            type_: type_,
+            entry: entry,
+        });
+    }
+
+    fn signal_abort(&self) {
+        if self.aborted.get() {
+            return;
+        }
+        self.aborted.set(true);
+
+        let roots = RootCollection::new();
+        loop {
+            let algorithm = match self.abort_algorithms.borrow_mut().pop() {
+                Some(algorithm) => algorithm,
+                None => break,
+            };
+            let target = algorithm.target.root(&roots);
+            target.remove_listener(algorithm.type_, &algorithm.entry);
+        }
+    }
+}
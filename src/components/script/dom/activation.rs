@@ -0,0 +1,118 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Activation behavior, as in http://dom.spec.whatwg.org/#eventtarget-activation-behavior
+//!
+//! Implemented by elements that have a default action in response to a
+//! `click` event (`HTMLInputElement`, `HTMLButtonElement`,
+//! `HTMLAnchorElement`, ...). `dispatch_event` locates the nearest
+//! activatable ancestor before dispatch and drives it through the three
+//! steps the spec describes, so synthetic and real clicks share this one
+//! path.
+
+use dom::bindings::codegen::InheritTypes::ElementCast;
+use dom::bindings::js::{JSRef, Root};
+use dom::element::Element;
+use dom::event::Event;
+use dom::eventtarget::EventTarget;
+use dom::htmlinputelement::HTMLInputElement;
+
+pub trait Activatable {
+    fn as_element<'a>(&'a self) -> JSRef<'a, Element>;
+
+    // Is this particular instance activatable? (e.g. a <input type=checkbox disabled> is not)
+    fn is_instance_activatable(&self) -> bool;
+
+    // http://dom.spec.whatwg.org/#eventtarget-legacy-pre-activation-behavior
+    // Tentatively perform the action (e.g. flip a checkbox's `checked`) so it can be
+    // seen by script running during the event, but reversibly, in case activation is canceled.
+    fn pre_click_activation(&self);
+
+    // http://dom.spec.whatwg.org/#eventtarget-canceled-activation-behavior
+    // Undo the tentative action performed by `pre_click_activation`.
+    fn canceled_activation(&self);
+
+    // http://dom.spec.whatwg.org/#eventtarget-activation-behavior
+    // Commit the tentative action (or run the element's default action directly,
+    // for elements with no pre-activation step, like link navigation or form submission).
+    // Takes the event that triggered activation so implementors that need it
+    // (e.g. to read the submitter of a form-associated element) can get at it.
+    fn activation_behavior(&self, event: &JSRef<Event>);
+}
+
+/// Downcast `element` to whichever concrete type (if any) implements
+/// `Activatable`, the way `dom::bindings::codegen::InheritTypes`'s `*Cast`
+/// helpers downcast `Node`/`EventTarget`. Returned as an enum rather than a
+/// trait object since a `JSRef` built from a local `downcast()` call
+/// doesn't outlive the method that produced it.
+pub enum Activations<'a> {
+    InputActivation(JSRef<'a, HTMLInputElement>),
+}
+
+impl<'a> Activatable for Activations<'a> {
+    fn as_element<'b>(&'b self) -> JSRef<'b, Element> {
+        match *self {
+            InputActivation(ref input) => input.as_element(),
+        }
+    }
+
+    fn is_instance_activatable(&self) -> bool {
+        match *self {
+            InputActivation(ref input) => input.is_instance_activatable(),
+        }
+    }
+
+    fn pre_click_activation(&self) {
+        match *self {
+            InputActivation(ref input) => input.pre_click_activation(),
+        }
+    }
+
+    fn canceled_activation(&self) {
+        match *self {
+            InputActivation(ref input) => input.canceled_activation(),
+        }
+    }
+
+    fn activation_behavior(&self, event: &JSRef<Event>) {
+        match *self {
+            InputActivation(ref input) => input.activation_behavior(event),
+        }
+    }
+}
+
+pub use self::Activations::InputActivation;
+
+pub trait ActivationElementHelpers<'a> {
+    fn as_maybe_activatable(&'a self) -> Option<Activations<'a>>;
+}
+
+impl<'a> ActivationElementHelpers<'a> for JSRef<'a, Element> {
+    fn as_maybe_activatable(&'a self) -> Option<Activations<'a>> {
+        self.downcast::<HTMLInputElement>().map(|input| InputActivation(input))
+    }
+}
+
+/// Walk from `target` up through the rooted ancestor `chain` that
+/// http://dom.spec.whatwg.org/#concept-event-dispatch builds, returning the
+/// nearest element (starting at `target` itself) that has activation
+/// behavior to run for a `click`.
+pub fn find_activatable_ancestor<'a>(target: &JSRef<'a, EventTarget>,
+                                     chain: &'a [Root<EventTarget>]) -> Option<JSRef<'a, Element>> {
+    if let Some(element) = ElementCast::to_ref(target) {
+        if element.as_maybe_activatable().map_or(false, |a| a.is_instance_activatable()) {
+            return Some(element.clone());
+        }
+    }
+
+    for ancestor in chain.iter() {
+        if let Some(element) = ElementCast::to_ref(ancestor.deref()) {
+            if element.as_maybe_activatable().map_or(false, |a| a.is_instance_activatable()) {
+                return Some(element.clone());
+            }
+        }
+    }
+
+    None
+}
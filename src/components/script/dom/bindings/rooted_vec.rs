@@ -0,0 +1,60 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! A `Vec` of `Root<T>` that unroots its contents in the correct order on
+//! `Drop`, instead of callers having to remember to do it by hand.
+//!
+//! `RootCollection` is a stack: each `Root<T>` pushes itself on construction
+//! and asserts it is the top entry when it pops itself on drop. A plain
+//! `Vec<Root<T>>` drops its elements front-to-back, which unregisters them in
+//! the wrong order as soon as it holds more than one entry. `RootedVec`
+//! contains that span of roots behind RAII, draining back-to-front (the
+//! order they were pushed, reversed) before its own `Vec` is torn down, so
+//! growing an ancestor chain (shadow trees, retargeting, ...) no longer
+//! needs a hand-written teardown loop at every call site.
+//!
+//! Untested here: `Root<T>` only exists on `dom::bindings::js`, which this
+//! tree doesn't define, so there's no way to construct one outside a real
+//! rooted context to exercise the drop order directly.
+
+use dom::bindings::js::Root;
+
+pub struct RootedVec<T> {
+    roots: Vec<Root<T>>,
+}
+
+impl<T> RootedVec<T> {
+    pub fn new() -> RootedVec<T> {
+        RootedVec { roots: vec!() }
+    }
+
+    pub fn push(&mut self, root: Root<T>) {
+        self.roots.push(root);
+    }
+
+    pub fn len(&self) -> uint {
+        self.roots.len()
+    }
+
+    pub fn iter<'a>(&'a self) -> ::std::slice::Items<'a, Root<T>> {
+        self.roots.iter()
+    }
+
+    pub fn rev_iter<'a>(&'a self) -> ::std::iter::Rev<::std::slice::Items<'a, Root<T>>> {
+        self.roots.iter().rev()
+    }
+
+    pub fn as_slice<'a>(&'a self) -> &'a [Root<T>] {
+        self.roots.as_slice()
+    }
+}
+
+#[unsafe_destructor]
+impl<T> Drop for RootedVec<T> {
+    fn drop(&mut self) {
+        // Unroot in the reverse of push order, i.e. the same order the
+        // hand-written `while chain.len() > 0 { chain.pop(); }` loop used to.
+        while self.roots.pop().is_some() {}
+    }
+}
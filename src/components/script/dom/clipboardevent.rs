@@ -0,0 +1,162 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::BindingDeclarations::ClipboardEventBinding;
+use dom::bindings::codegen::InheritTypes::{ClipboardEventDerived, EventCast};
+use dom::bindings::js::{JS, JSRef, Unrooted, OptionalRootable, RootCollection};
+use dom::bindings::utils::{Reflectable, reflect_dom_object};
+use dom::datatransfer::DataTransfer;
+use dom::event::{Event, EventTypeId, EventMethods, ClipboardEventTypeId};
+use dom::eventdispatcher::dispatch_event;
+use dom::eventtarget::EventTarget;
+use servo_util::str::DOMString;
+use util::clipboardprovider::ClipboardProvider;
+
+#[deriving(Encodable)]
+pub struct ClipboardEvent {
+    pub event: Event,
+    pub clipboard_data: Option<JS<DataTransfer>>,
+}
+
+impl ClipboardEventDerived for Event {
+    fn is_clipboardevent(&self) -> bool {
+        self.type_id == ClipboardEventTypeId
+    }
+}
+
+impl ClipboardEvent {
+    fn new_inherited(type_id: EventTypeId) -> ClipboardEvent {
+        ClipboardEvent {
+            event: Event::new_inherited(type_id),
+            clipboard_data: None,
+        }
+    }
+
+    pub fn new(window: &JSRef<::dom::window::Window>,
+               type_: DOMString,
+               can_bubble: bool,
+               cancelable: bool,
+               clipboard_data: Option<JSRef<DataTransfer>>) -> Unrooted<ClipboardEvent> {
+        let roots = RootCollection::new();
+        let mut ev = reflect_dom_object(~ClipboardEvent::new_inherited(ClipboardEventTypeId),
+                                        window,
+                                        ClipboardEventBinding::Wrap).root(&roots);
+        ev.clipboard_data = clipboard_data.map(|d| JS::from_rooted(&d));
+        {
+            let event: &mut JSRef<Event> = EventCast::from_mut_ref(&mut *ev);
+            event.InitEvent(type_, can_bubble, cancelable);
+        }
+        Unrooted::new_rooted(&*ev)
+    }
+}
+
+pub trait ClipboardEventMethods {
+    fn ClipboardData(&self) -> Option<Unrooted<DataTransfer>>;
+}
+
+impl<'a> ClipboardEventMethods for JSRef<'a, ClipboardEvent> {
+    // http://www.w3.org/TR/clipboard-apis/#widl-ClipboardEvent-clipboardData
+    fn ClipboardData(&self) -> Option<Unrooted<DataTransfer>> {
+        let roots = RootCollection::new();
+        self.clipboard_data.map(|d| Unrooted::new_rooted(&*d.root(&roots)))
+    }
+}
+
+impl Reflectable for ClipboardEvent {
+    fn reflector<'a>(&'a self) -> &'a ::dom::bindings::utils::Reflector {
+        self.event.reflector()
+    }
+
+    fn mut_reflector<'a>(&'a mut self) -> &'a mut ::dom::bindings::utils::Reflector {
+        self.event.mut_reflector()
+    }
+}
+
+// For "paste", the data has to be in place *before* dispatch: a paste
+// listener reads it off `event.clipboardData`, it doesn't write to it.
+// Kept as a plain predicate over the event type, rather than inlined into
+// `fire_clipboard_event`, so the read/write ordering is testable without
+// needing a rooted `JSRef`.
+fn reads_from_clipboard_before_dispatch(type_: &str) -> bool {
+    type_ == "paste"
+}
+
+// For "copy"/"cut", listeners write the data to place on the clipboard into
+// `event.clipboardData`; only commit it once dispatch finished without
+// `preventDefault()`.
+fn writes_to_clipboard_after_dispatch(type_: &str, canceled: bool) -> bool {
+    !canceled && (type_ == "copy" || type_ == "cut")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{reads_from_clipboard_before_dispatch, writes_to_clipboard_after_dispatch};
+
+    #[test]
+    fn only_paste_reads_before_dispatch() {
+        assert!(reads_from_clipboard_before_dispatch("paste"));
+        assert!(!reads_from_clipboard_before_dispatch("copy"));
+        assert!(!reads_from_clipboard_before_dispatch("cut"));
+    }
+
+    #[test]
+    fn copy_and_cut_write_after_dispatch_unless_canceled() {
+        assert!(writes_to_clipboard_after_dispatch("copy", false));
+        assert!(writes_to_clipboard_after_dispatch("cut", false));
+        assert!(!writes_to_clipboard_after_dispatch("copy", true));
+        assert!(!writes_to_clipboard_after_dispatch("paste", false));
+    }
+}
+
+/// Fire `copy`/`cut`/`paste` at `target`. For `copy`/`cut`, the system
+/// clipboard is written only once dispatch finished without
+/// `preventDefault()`. For `paste`, the system clipboard is read before
+/// dispatch, whether or not a listener then cancels the event.
+/// http://www.w3.org/TR/clipboard-apis/#integration-with-other-specs
+pub fn fire_clipboard_event(target: &JSRef<EventTarget>,
+                            window: &JSRef<::dom::window::Window>,
+                            type_: DOMString,
+                            clipboard_data: Option<JSRef<DataTransfer>>,
+                            provider: &mut ClipboardProvider) {
+    let roots = RootCollection::new();
+    let mut event = ClipboardEvent::new(window, type_.clone(), true, true, clipboard_data).root(&roots);
+
+    if reads_from_clipboard_before_dispatch(type_.as_slice()) {
+        let contents = provider.get_clipboard_contents();
+        if let Some(ref data) = event.clipboard_data {
+            let data = data.root(&roots);
+            data.set_data("text/plain".to_string(), contents);
+        }
+    }
+
+    let event_target: &mut JSRef<Event> = EventCast::from_mut_ref(&mut *event);
+    let not_canceled = dispatch_event(target, None, event_target);
+
+    if writes_to_clipboard_after_dispatch(type_.as_slice(), !not_canceled) {
+        if let Some(ref data) = event.clipboard_data {
+            let data = data.root(&roots);
+            provider.set_clipboard_contents(data.get_data("text/plain".to_string()));
+        }
+    }
+}
+
+/// http://www.w3.org/TR/clipboard-apis/#integration-with-other-specs --
+/// the entry point `Document::ExecCommand("copy"/"cut"/"paste")` dispatches
+/// through. `Document` (and the `execCommand`/keyboard-shortcut handling
+/// that would call this in a full build) lives outside this slice, so this
+/// is the concrete point a future caller hooks into.
+pub fn exec_copy_cut_paste(command: DOMString,
+                           target: &JSRef<EventTarget>,
+                           window: &JSRef<::dom::window::Window>,
+                           provider: &mut ClipboardProvider) -> bool {
+    let roots = RootCollection::new();
+    match command.as_slice() {
+        "copy" | "cut" | "paste" => {
+            let data = DataTransfer::new(window).root(&roots);
+            fire_clipboard_event(target, window, command, Some(data.deref().clone()), provider);
+            true
+        }
+        _ => false,
+    }
+}
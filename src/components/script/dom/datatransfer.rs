@@ -0,0 +1,74 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! http://www.w3.org/TR/clipboard-apis/#datatransfer-interface-model
+//!
+//! Only the plain-text string model `ClipboardEvent` needs is implemented;
+//! the file/item list model isn't used by clipboard events.
+
+use dom::bindings::codegen::BindingDeclarations::DataTransferBinding;
+use dom::bindings::js::{JSRef, Unrooted};
+use dom::bindings::utils::{Reflector, Reflectable, reflect_dom_object};
+use dom::window::Window;
+use servo_util::str::DOMString;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+pub struct DataTransfer {
+    pub reflector_: Reflector,
+    items: RefCell<HashMap<DOMString, DOMString>>,
+}
+
+impl DataTransfer {
+    pub fn new_inherited() -> DataTransfer {
+        DataTransfer {
+            reflector_: Reflector::new(),
+            items: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn new(window: &JSRef<Window>) -> Unrooted<DataTransfer> {
+        reflect_dom_object(~DataTransfer::new_inherited(), window, DataTransferBinding::Wrap)
+    }
+
+    // http://www.w3.org/TR/clipboard-apis/#widl-DataTransfer-getData-DOMString-DOMString-format
+    pub fn get_data(&self, format: DOMString) -> DOMString {
+        self.items.borrow().find(&format).map(|data| data.clone()).unwrap_or_else(|| "".to_string())
+    }
+
+    // http://www.w3.org/TR/clipboard-apis/#widl-DataTransfer-setData-void-DOMString-format-DOMString-data
+    pub fn set_data(&self, format: DOMString, data: DOMString) {
+        self.items.borrow_mut().insert(format, data);
+    }
+}
+
+impl Reflectable for DataTransfer {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        &self.reflector_
+    }
+
+    fn mut_reflector<'a>(&'a mut self) -> &'a mut Reflector {
+        &mut self.reflector_
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DataTransfer;
+
+    #[test]
+    fn get_data_defaults_to_the_empty_string() {
+        let data = DataTransfer::new_inherited();
+        assert_eq!(data.get_data("text/plain".to_string()).as_slice(), "");
+    }
+
+    #[test]
+    fn set_data_is_visible_to_a_later_get_data_for_the_same_format() {
+        let data = DataTransfer::new_inherited();
+        data.set_data("text/plain".to_string(), "hello".to_string());
+        assert_eq!(data.get_data("text/plain".to_string()).as_slice(), "hello");
+        // A different format wasn't touched.
+        assert_eq!(data.get_data("text/html".to_string()).as_slice(), "");
+    }
+}
@@ -127,43 +127,19 @@ impl<'a> DOMImplementationMethods for JSRef<'a, DOMImplementation> {
 
         // Step 1-2.
         let mut doc = Document::new(&owner.root_ref(), None, HTMLDocument, None).root(&roots);
-        let mut doc_alias = doc.clone();
-        let doc_node: &mut JSRef<Node> = NodeCast::from_mut_ref(&mut doc_alias);
 
-        {
-            // Step 3.
-            let mut doc_type = DocumentType::new(~"html", None, None, &*doc).root(&roots);
-            assert!(doc_node.AppendChild(NodeCast::from_mut_ref(&mut *doc_type)).is_ok());
-        }
-
-        {
-            // Step 4.
-            let mut doc_html = NodeCast::from_unrooted(HTMLHtmlElement::new(~"html", &*doc)).root(&roots);
-            assert!(doc_node.AppendChild(&mut *doc_html).is_ok());
-
-            {
-                // Step 5.
-                let mut doc_head = NodeCast::from_unrooted(HTMLHeadElement::new(~"head", &*doc)).root(&roots);
-                assert!(doc_html.AppendChild(&mut *doc_head).is_ok());
-
-                // Step 6.
-                match title {
-                    None => (),
-                    Some(title_str) => {
-                        // Step 6.1.
-                        let mut doc_title = NodeCast::from_unrooted(HTMLTitleElement::new(~"title", &*doc)).root(&roots);
-                        assert!(doc_head.AppendChild(&mut *doc_title).is_ok());
-
-                        // Step 6.2.
-                        let mut title_text = Text::new(title_str, &*doc).root(&roots);
-                        assert!(doc_title.AppendChild(NodeCast::from_mut_ref(&mut *title_text)).is_ok());
-                    }
-                }
-            }
+        // Steps 3-5, 7.
+        let (mut doc_head, _) = create_html_document_skeleton(&*doc);
 
-            // Step 7.
-            let mut doc_body = HTMLBodyElement::new(~"body", &*doc).root(&roots);
-            assert!(doc_html.AppendChild(NodeCast::from_mut_ref(&mut *doc_body)).is_ok());
+        // Step 6.
+        if let Some(title_str) = title {
+            // Step 6.1.
+            let mut doc_title = NodeCast::from_unrooted(HTMLTitleElement::new(~"title", &*doc)).root(&roots);
+            assert!(doc_head.AppendChild(&mut *doc_title).is_ok());
+
+            // Step 6.2.
+            let mut title_text = Text::new(title_str, &*doc).root(&roots);
+            assert!(doc_title.AppendChild(NodeCast::from_mut_ref(&mut *title_text)).is_ok());
         }
 
         // Step 8.
@@ -173,3 +149,30 @@ impl<'a> DOMImplementationMethods for JSRef<'a, DOMImplementation> {
         Unrooted::new_rooted(&*doc)
     }
 }
+
+/// Build the implicit `<!DOCTYPE html><html><head></head><body></body></html>`
+/// skeleton a new HTML document always starts with (steps 3-5 and 7 of
+/// `CreateHTMLDocument`), returning the `<head>` and `<body>` nodes so a
+/// caller can keep inserting into either one. Shared with the HTML tree
+/// builder in `servohtmlparser`, which needs the same implicit insertion
+/// `parseFromString("...", "text/html")` relies on.
+pub fn create_html_document_skeleton(doc: &JSRef<Document>) -> (Root<Node>, Root<Node>) {
+    let roots = RootCollection::new();
+    let doc_node: &JSRef<Node> = NodeCast::from_ref(doc);
+
+    {
+        let mut doc_type = DocumentType::new(~"html", None, None, doc).root(&roots);
+        assert!(doc_node.clone().AppendChild(NodeCast::from_mut_ref(&mut *doc_type)).is_ok());
+    }
+
+    let mut doc_html = NodeCast::from_unrooted(HTMLHtmlElement::new(~"html", doc)).root(&roots);
+    assert!(doc_node.clone().AppendChild(&mut *doc_html).is_ok());
+
+    let doc_head = NodeCast::from_unrooted(HTMLHeadElement::new(~"head", doc)).root(&roots);
+    assert!(doc_html.clone().AppendChild(&mut doc_head.clone()).is_ok());
+
+    let doc_body = NodeCast::from_unrooted(HTMLBodyElement::new(~"body", doc)).root(&roots);
+    assert!(doc_html.clone().AppendChild(&mut doc_body.clone()).is_ok());
+
+    (doc_head, doc_body)
+}
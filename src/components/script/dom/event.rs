@@ -0,0 +1,158 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! http://dom.spec.whatwg.org/#interface-event
+
+use dom::bindings::js::{JS, JSRef, OptionalAssignable, OptionalRootable, Unrooted, RootCollection};
+use dom::bindings::utils::{Reflector, Reflectable};
+use dom::eventtarget::EventTarget;
+use servo_util::str::DOMString;
+
+#[deriving(PartialEq, Encodable)]
+pub enum EventTypeId {
+    HTMLEventTypeId,
+    ClipboardEventTypeId,
+}
+
+pub use self::EventTypeId::{HTMLEventTypeId, ClipboardEventTypeId};
+
+#[deriving(PartialEq, Encodable)]
+pub enum EventPhase {
+    PhaseNone,
+    PhaseCapturing,
+    PhaseAtTarget,
+    PhaseBubbling,
+}
+
+pub use self::EventPhase::{PhaseNone, PhaseCapturing, PhaseAtTarget, PhaseBubbling};
+
+#[deriving(Encodable)]
+pub struct Event {
+    pub reflector_: Reflector,
+    pub type_id: EventTypeId,
+    pub current_target: Option<JS<EventTarget>>,
+    pub target: Option<JS<EventTarget>>,
+    pub type_: DOMString,
+    pub phase: EventPhase,
+    pub canceled: bool,
+    pub stop_propagation: bool,
+    pub stop_immediate: bool,
+    pub cancelable: bool,
+    pub bubbles: bool,
+    pub dispatching: bool,
+    pub initialized: bool,
+    /// Set by `dispatch_event` for the duration of a listener registered
+    /// with `{passive: true}`. http://dom.spec.whatwg.org/#dom-event-preventdefault
+    /// has `PreventDefault` no-op while it's set, since a passive listener
+    /// has promised not to call `preventDefault()`.
+    pub in_passive_listener: bool,
+}
+
+impl Event {
+    pub fn new_inherited(type_id: EventTypeId) -> Event {
+        Event {
+            reflector_: Reflector::new(),
+            type_id: type_id,
+            current_target: None,
+            target: None,
+            type_: "".to_string(),
+            phase: PhaseNone,
+            canceled: false,
+            stop_propagation: false,
+            stop_immediate: false,
+            cancelable: false,
+            bubbles: false,
+            dispatching: false,
+            initialized: false,
+            in_passive_listener: false,
+        }
+    }
+}
+
+impl Reflectable for Event {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        &self.reflector_
+    }
+
+    fn mut_reflector<'a>(&'a mut self) -> &'a mut Reflector {
+        &mut self.reflector_
+    }
+}
+
+pub trait EventMethods {
+    fn Type(&self) -> DOMString;
+    fn GetTarget(&self) -> Option<Unrooted<EventTarget>>;
+    fn GetCurrentTarget(&self) -> Option<Unrooted<EventTarget>>;
+    fn DefaultPrevented(&self) -> bool;
+    fn PreventDefault(&mut self);
+    fn StopPropagation(&mut self);
+    fn StopImmediatePropagation(&mut self);
+    fn Bubbles(&self) -> bool;
+    fn Cancelable(&self) -> bool;
+    fn InitEvent(&mut self, type_: DOMString, bubbles: bool, cancelable: bool);
+}
+
+impl<'a> EventMethods for JSRef<'a, Event> {
+    fn Type(&self) -> DOMString {
+        self.type_.clone()
+    }
+
+    fn GetTarget(&self) -> Option<Unrooted<EventTarget>> {
+        let roots = RootCollection::new();
+        self.target.map(|target| Unrooted::new_rooted(&*target.root(&roots)))
+    }
+
+    fn GetCurrentTarget(&self) -> Option<Unrooted<EventTarget>> {
+        let roots = RootCollection::new();
+        self.current_target.map(|target| Unrooted::new_rooted(&*target.root(&roots)))
+    }
+
+    fn DefaultPrevented(&self) -> bool {
+        self.canceled
+    }
+
+    // http://dom.spec.whatwg.org/#dom-event-preventdefault
+    fn PreventDefault(&mut self) {
+        // Step 1: a passive listener promised not to cancel the event; honor
+        // that regardless of what it actually calls.
+        if self.in_passive_listener {
+            return;
+        }
+        // Step 2.
+        if self.cancelable {
+            self.canceled = true;
+        }
+    }
+
+    fn StopPropagation(&mut self) {
+        self.stop_propagation = true;
+    }
+
+    fn StopImmediatePropagation(&mut self) {
+        self.stop_propagation = true;
+        self.stop_immediate = true;
+    }
+
+    fn Bubbles(&self) -> bool {
+        self.bubbles
+    }
+
+    fn Cancelable(&self) -> bool {
+        self.cancelable
+    }
+
+    // http://dom.spec.whatwg.org/#dom-event-initevent
+    fn InitEvent(&mut self, type_: DOMString, bubbles: bool, cancelable: bool) {
+        if self.dispatching {
+            return;
+        }
+        self.initialized = true;
+        self.stop_propagation = false;
+        self.stop_immediate = false;
+        self.canceled = false;
+        self.type_ = type_;
+        self.bubbles = bubbles;
+        self.cancelable = cancelable;
+    }
+}
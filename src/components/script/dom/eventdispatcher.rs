@@ -2,12 +2,52 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use dom::activation::{Activatable, ActivationElementHelpers, find_activatable_ancestor};
 use dom::bindings::callback::ReportExceptions;
 use dom::bindings::codegen::InheritTypes::{EventTargetCast, NodeCast, NodeDerived};
-use dom::bindings::js::{JSRef, OptionalAssignable, RootCollection, Root};
-use dom::eventtarget::{Capturing, Bubbling, EventTarget};
+use dom::bindings::js::{JSRef, OptionalAssignable, OptionalRootable, RootCollection};
+use dom::bindings::rooted_vec::RootedVec;
+use dom::eventtarget::{Capturing, Bubbling, EventListenerEntry, EventTarget, EventTargetHelpers};
 use dom::event::{Event, PhaseAtTarget, PhaseNone, PhaseBubbling, PhaseCapturing, EventMethods};
 use dom::node::{Node, NodeHelpers};
+use servo_util::str::DOMString;
+
+// Invoke one phase's listeners for `cur_target`, honoring `once` (remove the
+// listener once it has fired, collected here and removed after this call's
+// listeners have run so the list being walked is never mutated) and `passive`
+// (Event::PreventDefault consults `in_passive_listener` and no-ops while
+// it's set, so a passive listener can't cancel the event for this call).
+// Returns whether `stopPropagation()` was called.
+fn invoke_listeners(cur_target: &JSRef<EventTarget>,
+                    entries: Vec<EventListenerEntry>,
+                    type_: DOMString,
+                    event: &mut JSRef<Event>) -> bool {
+    let mut fired_once = vec!();
+
+    for entry in entries.iter() {
+        event.get_mut().in_passive_listener = entry.passive;
+
+        //FIXME: this should have proper error handling, or explicitly
+        //       drop the exception on the floor
+        assert!(entry.listener.HandleEvent__(event, ReportExceptions).is_ok());
+
+        event.get_mut().in_passive_listener = false;
+
+        if entry.once {
+            fired_once.push(entry.clone());
+        }
+
+        if event.get().stop_immediate {
+            break;
+        }
+    }
+
+    for entry in fired_once.iter() {
+        cur_target.remove_listener(type_.clone(), entry);
+    }
+
+    event.get().stop_propagation
+}
 
 // See http://dom.spec.whatwg.org/#concept-event-dispatch for the full dispatch algorithm
 pub fn dispatch_event<'a, 'b>(target: &JSRef<'a, EventTarget>,
@@ -28,36 +68,43 @@ pub fn dispatch_event<'a, 'b>(target: &JSRef<'a, EventTarget>,
     let type_ = event.get().type_.clone();
 
     //TODO: no chain if not participating in a tree
-    let mut chain: Vec<Root<EventTarget>> = if target.get().is_node() {
+    let mut chain: RootedVec<EventTarget> = RootedVec::new();
+    if target.get().is_node() {
         let target_node: &JSRef<Node> = NodeCast::to_ref(target).unwrap();
-        target_node.ancestors().map(|ancestor| {
+        for ancestor in target_node.ancestors() {
             let ancestor_target: &JSRef<EventTarget> = EventTargetCast::from_ref(&ancestor);
-            ancestor_target.unrooted().root(&roots)
-        }).collect()
+            chain.push(ancestor_target.unrooted().root(&roots));
+        }
+    }
+
+    // http://dom.spec.whatwg.org/#eventtarget-legacy-pre-activation-behavior
+    // Locate the nearest activatable ancestor (the target itself, or the first
+    // ancestor in `chain` with activation behavior) and give it a chance to
+    // tentatively run its default action before any listener sees the event.
+    let activation_target = if type_.as_slice() == "click" {
+        find_activatable_ancestor(target, chain.as_slice())
     } else {
-        vec!()
+        None
     };
+    match activation_target {
+        Some(ref activatable) => {
+            if let Some(activatable) = activatable.as_maybe_activatable() {
+                activatable.pre_click_activation();
+            }
+        }
+        None => ()
+    }
 
     event.get_mut().phase = PhaseCapturing;
 
     //FIXME: The "callback this value" should be currentTarget
 
     /* capturing */
-    for cur_target in chain.as_slice().rev_iter() {
-        let stopped = match cur_target.get_listeners_for(type_, Capturing) {
+    for cur_target in chain.rev_iter() {
+        let stopped = match cur_target.get_listeners_for(type_.clone(), Capturing) {
             Some(listeners) => {
                 event.current_target.assign(Some(cur_target.deref().clone()));
-                for listener in listeners.iter() {
-                    //FIXME: this should have proper error handling, or explicitly
-                    //       drop the exception on the floor
-                    assert!(listener.HandleEvent__(event, ReportExceptions).is_ok());
-
-                    if event.get().stop_immediate {
-                        break;
-                    }
-                }
-
-                event.get().stop_propagation
+                invoke_listeners(cur_target.deref(), listeners, type_.clone(), event)
             }
             None => false
         };
@@ -75,16 +122,9 @@ pub fn dispatch_event<'a, 'b>(target: &JSRef<'a, EventTarget>,
             event.current_target.assign(Some(target.clone()));
         }
 
-        let opt_listeners = target.get().get_listeners(type_);
-        for listeners in opt_listeners.iter() {
-            for listener in listeners.iter() {
-                //FIXME: this should have proper error handling, or explicitly drop the
-                //       exception on the floor.
-                assert!(listener.HandleEvent__(event, ReportExceptions).is_ok());
-                if event.get().stop_immediate {
-                    break;
-                }
-            }
+        let opt_listeners = target.get().get_listeners(type_.clone());
+        for listeners in opt_listeners.into_iter() {
+            invoke_listeners(target, listeners, type_.clone(), event);
         }
     }
 
@@ -93,20 +133,10 @@ pub fn dispatch_event<'a, 'b>(target: &JSRef<'a, EventTarget>,
         event.get_mut().phase = PhaseBubbling;
 
         for cur_target in chain.iter() {
-            let stopped = match cur_target.get().get_listeners_for(type_, Bubbling) {
+            let stopped = match cur_target.get().get_listeners_for(type_.clone(), Bubbling) {
                 Some(listeners) => {
                     event.get_mut().current_target.assign(Some(cur_target.deref().clone()));
-                    for listener in listeners.iter() {
-                        //FIXME: this should have proper error handling or explicitly
-                        //       drop exceptions on the floor.
-                        assert!(listener.HandleEvent__(event, ReportExceptions).is_ok());
-
-                        if event.get().stop_immediate {
-                            break;
-                        }
-                    }
-
-                    event.get().stop_propagation
+                    invoke_listeners(cur_target.deref(), listeners, type_.clone(), event)
                 }
                 None => false
             };
@@ -116,10 +146,20 @@ pub fn dispatch_event<'a, 'b>(target: &JSRef<'a, EventTarget>,
         }
     }
 
-    // Root ordering restrictions mean we need to unroot the chain entries
-    // in the same order they were rooted.
-    while chain.len() > 0 {
-        let _ = chain.pop();
+    // http://dom.spec.whatwg.org/#eventtarget-activation-behavior /
+    // http://dom.spec.whatwg.org/#eventtarget-canceled-activation-behavior
+    // Run after the bubble phase so script had a chance to call preventDefault().
+    match activation_target {
+        Some(ref activatable) => {
+            if let Some(activatable) = activatable.as_maybe_activatable() {
+                if !event.DefaultPrevented() {
+                    activatable.activation_behavior(&*event);
+                } else {
+                    activatable.canceled_activation();
+                }
+            }
+        }
+        None => ()
     }
 
     event.dispatching = false;
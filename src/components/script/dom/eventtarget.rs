@@ -0,0 +1,165 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! http://dom.spec.whatwg.org/#interface-eventtarget
+//!
+//! `once` removal and `AbortSignal`-triggered removal work on
+//! `EventListenerEntry` values, which need a real `EventListener`
+//! (from `dom::bindings::callback`) and `RootCollection` to construct in a
+//! test; neither is a real file in this tree, so this logic is exercised by
+//! `dispatch_event`'s callers rather than by unit tests here.
+
+use dom::abortsignal::{AbortSignal, AbortSignalHelpers};
+use dom::bindings::callback::EventListener;
+use dom::bindings::js::JSRef;
+use dom::bindings::utils::{Reflector, Reflectable};
+use servo_util::str::DOMString;
+use std::collections::HashMap;
+use std::cell::RefCell;
+
+#[deriving(PartialEq, Clone)]
+pub enum ListenerPhase {
+    Capturing,
+    Bubbling,
+}
+
+pub use self::ListenerPhase::{Capturing, Bubbling};
+
+/// A single entry in a target's listener list for one event type.
+/// http://dom.spec.whatwg.org/#concept-event-listener
+#[deriving(Clone)]
+pub struct EventListenerEntry {
+    pub listener: EventListener,
+    pub phase: ListenerPhase,
+    pub once: bool,
+    pub passive: bool,
+}
+
+pub struct EventTarget {
+    pub reflector_: Reflector,
+    handlers: RefCell<HashMap<DOMString, Vec<EventListenerEntry>>>,
+}
+
+impl EventTarget {
+    pub fn new_inherited() -> EventTarget {
+        EventTarget {
+            reflector_: Reflector::new(),
+            handlers: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl Reflectable for EventTarget {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        &self.reflector_
+    }
+
+    fn mut_reflector<'a>(&'a mut self) -> &'a mut Reflector {
+        &mut self.reflector_
+    }
+}
+
+pub trait EventTargetHelpers {
+    fn get_listeners(&self, type_: DOMString) -> Option<Vec<EventListenerEntry>>;
+    fn get_listeners_for(&self, type_: DOMString, phase: ListenerPhase) -> Option<Vec<EventListenerEntry>>;
+
+    /// Remove a listener that matched by `(callback, phase)`. Called once a
+    /// `once` listener has fired, or when an `AbortSignal` passed to
+    /// `AddEventListener` aborts; never called mid-iteration of a phase's
+    /// listener list, since mutating it while it's being walked would
+    /// invalidate the snapshot `dispatch_event` is iterating.
+    fn remove_listener(&self, type_: DOMString, entry: &EventListenerEntry);
+}
+
+impl EventTargetHelpers for EventTarget {
+    fn get_listeners(&self, type_: DOMString) -> Option<Vec<EventListenerEntry>> {
+        self.handlers.borrow().find(&type_).map(|entries| entries.clone())
+    }
+
+    fn get_listeners_for(&self, type_: DOMString, phase: ListenerPhase) -> Option<Vec<EventListenerEntry>> {
+        self.handlers.borrow().find(&type_).map(|entries| {
+            entries.iter().filter(|entry| entry.phase == phase).map(|entry| entry.clone()).collect()
+        })
+    }
+
+    fn remove_listener(&self, type_: DOMString, entry: &EventListenerEntry) {
+        let mut handlers = self.handlers.borrow_mut();
+        if let Some(entries) = handlers.find_mut(&type_) {
+            entries.retain(|e| !(e.listener == entry.listener && e.phase == entry.phase));
+        }
+    }
+}
+
+pub trait EventTargetMethods {
+    // http://dom.spec.whatwg.org/#dom-eventtarget-addeventlistener
+    fn AddEventListener(&self,
+                        type_: DOMString,
+                        listener: Option<EventListener>,
+                        capture: bool,
+                        once: bool,
+                        passive: bool,
+                        signal: Option<JSRef<AbortSignal>>);
+
+    // http://dom.spec.whatwg.org/#dom-eventtarget-removeeventlistener
+    fn RemoveEventListener(&self, type_: DOMString, listener: Option<EventListener>, capture: bool);
+}
+
+impl<'a> EventTargetMethods for JSRef<'a, EventTarget> {
+    fn AddEventListener(&self,
+                        type_: DOMString,
+                        listener: Option<EventListener>,
+                        capture: bool,
+                        once: bool,
+                        passive: bool,
+                        signal: Option<JSRef<AbortSignal>>) {
+        let listener = match listener {
+            Some(listener) => listener,
+            None => return,
+        };
+
+        // http://dom.spec.whatwg.org/#add-an-event-listener, step 3:
+        // an already-aborted signal means the listener is never added at all.
+        if signal.map_or(false, |signal| signal.Aborted()) {
+            return;
+        }
+
+        let entry = EventListenerEntry {
+            listener: listener,
+            phase: if capture { Capturing } else { Bubbling },
+            once: once,
+            passive: passive,
+        };
+
+        {
+            let mut handlers = self.handlers.borrow_mut();
+            let entries = handlers.find_or_insert_with(type_.clone(), |_| vec!());
+            if entries.iter().any(|e| e.listener == entry.listener && e.phase == entry.phase) {
+                return;
+            }
+            entries.push(entry.clone());
+        }
+
+        // http://dom.spec.whatwg.org/#add-an-event-listener
+        // step 7: removing the listener *is* the abort algorithm, run
+        // whenever `signal` aborts (not just if it's already aborted, which
+        // step 3 above already handled by returning before registration).
+        if let Some(signal) = signal {
+            signal.add_abort_algorithm(self.clone(), type_, entry);
+        }
+    }
+
+    fn RemoveEventListener(&self, type_: DOMString, listener: Option<EventListener>, capture: bool) {
+        let listener = match listener {
+            Some(listener) => listener,
+            None => return,
+        };
+        let entry = EventListenerEntry {
+            listener: listener,
+            phase: if capture { Capturing } else { Bubbling },
+            once: false,
+            passive: false,
+        };
+        self.remove_listener(type_, &entry);
+    }
+}
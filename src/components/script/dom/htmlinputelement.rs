@@ -0,0 +1,107 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! https://html.spec.whatwg.org/multipage/#the-input-element
+//!
+//! Only the checkbox activation behavior
+//! (http://dom.spec.whatwg.org/#eventtarget-activation-behavior) is modeled
+//! here; `HTMLInputElement`'s attribute/layout surface lives outside this
+//! slice.
+
+use dom::activation::Activatable;
+use dom::bindings::codegen::InheritTypes::ElementCast;
+use dom::bindings::js::JSRef;
+use dom::element::Element;
+use dom::event::Event;
+use std::cell::Cell;
+
+#[deriving(Encodable)]
+pub struct HTMLInputElement {
+    checked: Cell<bool>,
+    checked_before_activation: Cell<bool>,
+}
+
+impl HTMLInputElement {
+    pub fn new_inherited() -> HTMLInputElement {
+        HTMLInputElement {
+            checked: Cell::new(false),
+            checked_before_activation: Cell::new(false),
+        }
+    }
+
+    pub fn Checked(&self) -> bool {
+        self.checked.get()
+    }
+
+    pub fn SetChecked(&self, checked: bool) {
+        self.checked.set(checked);
+    }
+
+    // http://dom.spec.whatwg.org/#eventtarget-legacy-pre-activation-behavior
+    // Tentatively flip `checked` so a listener running during the click can
+    // already see the new state, remembering the old one in case the click
+    // is canceled. Kept on the plain struct, rather than inlined into the
+    // `Activatable` impl below, so the toggle-and-restore logic is testable
+    // without needing a rooted `JSRef`.
+    fn do_pre_click_activation(&self) {
+        self.checked_before_activation.set(self.checked.get());
+        self.checked.set(!self.checked.get());
+    }
+
+    // http://dom.spec.whatwg.org/#eventtarget-canceled-activation-behavior
+    fn do_canceled_activation(&self) {
+        self.checked.set(self.checked_before_activation.get());
+    }
+}
+
+impl<'a> Activatable for JSRef<'a, HTMLInputElement> {
+    fn as_element<'b>(&'b self) -> JSRef<'b, Element> {
+        ElementCast::from_ref(self).clone()
+    }
+
+    // A checkbox is activatable unless it's disabled; `disabled` attribute
+    // handling lives on `Element`, outside this slice, so this always
+    // returns true for now.
+    fn is_instance_activatable(&self) -> bool {
+        true
+    }
+
+    fn pre_click_activation(&self) {
+        self.do_pre_click_activation();
+    }
+
+    fn canceled_activation(&self) {
+        self.do_canceled_activation();
+    }
+
+    // http://dom.spec.whatwg.org/#eventtarget-activation-behavior
+    // The tentative toggle from `pre_click_activation` already committed;
+    // nothing further to do here besides letting the click finish.
+    fn activation_behavior(&self, _event: &JSRef<Event>) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::HTMLInputElement;
+
+    #[test]
+    fn pre_click_activation_toggles_checked() {
+        let input = HTMLInputElement::new_inherited();
+        assert!(!input.Checked());
+        input.do_pre_click_activation();
+        assert!(input.Checked());
+        input.do_pre_click_activation();
+        assert!(!input.Checked());
+    }
+
+    #[test]
+    fn canceled_activation_restores_the_pre_click_value() {
+        let input = HTMLInputElement::new_inherited();
+        input.SetChecked(true);
+        input.do_pre_click_activation();
+        assert!(!input.Checked());
+        input.do_canceled_activation();
+        assert!(input.Checked());
+    }
+}
@@ -0,0 +1,532 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! A tokenizer-driven tree-construction sink, shared by the HTML and XML
+//! tree builders invoked from `DOMParser::ParseFromString` (and, later,
+//! `innerHTML` and `XMLHttpRequest.responseXML`).
+//!
+//! This mirrors the operations a tree builder expects to call while it
+//! consumes tokens: creating nodes, inserting them relative to a parent
+//! or sibling, and the handful of document-level queries (template
+//! contents, node identity) the builder needs to make insertion
+//! decisions. It is deliberately built out of the same primitives
+//! `DOMImplementation` already uses (`Element`/`CreateElementNS`, `Text`,
+//! `DocumentType::new`, `AppendChild`) so the two entry points stay
+//! consistent. `parse_html`/`parse_xml` below are a deliberately small
+//! tokenizer that drives this sink; they do not implement the full
+//! HTML5/XML tokenization state machines, just enough tag/text/comment/
+//! doctype recognition to build a real tree through it.
+
+use dom::bindings::codegen::InheritTypes::NodeCast;
+use dom::bindings::js::{JS, JSRef, Root, RootCollection, OptionalRootable, Unrooted};
+use dom::comment::Comment;
+use dom::document::{Document, DocumentMethods};
+use dom::documenttype::DocumentType;
+use dom::domimplementation::create_html_document_skeleton;
+use dom::element::{Element, ElementMethods};
+use dom::htmltemplateelement::HTMLTemplateElement;
+use dom::node::{Node, NodeMethods};
+use dom::text::Text;
+use servo_util::str::DOMString;
+
+/// Either a node built elsewhere in the tree, or a run of text to be
+/// appended as a `Text` node (merging with a preceding text node sibling
+/// where the DOM requires it).
+pub enum NodeOrText {
+    AppendNode(JS<Node>),
+    AppendText(DOMString),
+}
+
+/// The set of operations a tree builder performs against a `Document`
+/// while it consumes tokens; `parse_html`/`parse_xml` drive a builder
+/// through this sink, and nothing here knows about tokens or parse errors.
+pub struct Sink {
+    pub document: JS<Document>,
+}
+
+impl Sink {
+    pub fn new(document: &JSRef<Document>) -> Sink {
+        Sink {
+            document: JS::from_rooted(document),
+        }
+    }
+
+    fn root(&self, roots: &RootCollection) -> Root<Document> {
+        self.document.root(roots)
+    }
+
+    // Create an element for `qname`, the way `DOMImplementation::CreateDocument`
+    // creates its document element (via `CreateElementNS`). Tag soup is, by
+    // definition, not guaranteed to be well-formed: `None` means `qname`
+    // wasn't a valid qualified name, which the caller should treat as "drop
+    // this token" rather than a reason to crash the parser. An attribute
+    // whose name doesn't survive `SetAttribute` is similarly just dropped,
+    // not fatal to the rest of the tag.
+    pub fn create_element(&self, qname: DOMString, attrs: Vec<(DOMString, DOMString)>) -> Option<Unrooted<Element>> {
+        let roots = RootCollection::new();
+        let mut document = self.root(&roots);
+        let mut elem = match document.CreateElementNS(None, qname).ok() {
+            Some(elem) => elem.root(&roots),
+            None => return None,
+        };
+        for (name, value) in attrs.into_iter() {
+            let _ = elem.SetAttribute(name, value);
+        }
+        Some(Unrooted::new_rooted(&*elem))
+    }
+
+    pub fn create_comment(&self, text: DOMString) -> Unrooted<Comment> {
+        let roots = RootCollection::new();
+        let document = self.root(&roots);
+        Comment::new(text, &*document)
+    }
+
+    // Append `child` to `parent`, merging into a trailing text node when
+    // `child` is text and the parent's last child is already text.
+    pub fn append(&self, parent: JSRef<Node>, child: NodeOrText) {
+        let roots = RootCollection::new();
+        match child {
+            AppendNode(node) => {
+                let mut node = node.root(&roots);
+                assert!(parent.clone().AppendChild(&mut *node).is_ok());
+            }
+            AppendText(text) => {
+                if let Some(last_child) = parent.GetLastChild() {
+                    let last_child = last_child.root(&roots);
+                    if let Some(existing) = last_child.downcast::<Text>() {
+                        existing.characterdata().data.borrow_mut().push_str(text.as_slice());
+                        return;
+                    }
+                }
+                let document = self.root(&roots);
+                let mut text_node = Text::new(text, &*document).root(&roots);
+                assert!(parent.clone().AppendChild(NodeCast::from_mut_ref(&mut *text_node)).is_ok());
+            }
+        }
+    }
+
+    // http://dom.spec.whatwg.org/#dom-domimplementation-createdocumenttype,
+    // invoked by the tree builder instead of script for the synthesized
+    // doctype that precedes the root element.
+    pub fn append_doctype_to_document(&self, name: DOMString, public_id: DOMString, system_id: DOMString) {
+        let roots = RootCollection::new();
+        let document = self.root(&roots);
+        let mut doctype = DocumentType::new(name, Some(public_id), Some(system_id), &*document).root(&roots);
+        let doc_node: &JSRef<Node> = NodeCast::from_ref(&*document);
+        assert!(doc_node.clone().AppendChild(NodeCast::from_mut_ref(&mut *doctype)).is_ok());
+    }
+
+    // Insert `child` immediately before `sibling`, used when the tree
+    // builder has to fix up misnested formatting elements.
+    pub fn append_before_sibling(&self, sibling: JSRef<Node>, child: NodeOrText) {
+        let roots = RootCollection::new();
+        let mut parent = sibling.GetParentNode().expect("append_before_sibling: sibling has no parent").root(&roots);
+        match child {
+            AppendNode(node) => {
+                let mut node = node.root(&roots);
+                assert!(parent.InsertBefore(&mut *node, Some(sibling)).is_ok());
+            }
+            AppendText(text) => {
+                let document = self.root(&roots);
+                let mut text_node = Text::new(text, &*document).root(&roots);
+                assert!(parent.InsertBefore(NodeCast::from_mut_ref(&mut *text_node), Some(sibling)).is_ok());
+            }
+        }
+    }
+
+    // `<template>` contents live in a separate document fragment; the
+    // tree builder inserts into that fragment rather than the element
+    // itself.
+    pub fn get_template_contents(&self, target: JSRef<Element>) -> JS<Node> {
+        let template = target.downcast::<HTMLTemplateElement>()
+                              .expect("get_template_contents called on a non-template element");
+        JS::from_rooted(&NodeCast::from_ref(&template.Content()))
+    }
+
+    pub fn same_node(&self, x: JSRef<Node>, y: JSRef<Node>) -> bool {
+        x.eq(&y)
+    }
+
+    // Move all children of `node` onto `new_parent`, used by
+    // `render_parser_error` to move the partial tree under the
+    // `parsererror` element.
+    pub fn reparent_children(&self, node: JSRef<Node>, new_parent: JSRef<Node>) {
+        let roots = RootCollection::new();
+        while let Some(child) = node.GetFirstChild() {
+            let mut child = child.root(&roots);
+            assert!(new_parent.clone().AppendChild(&mut *child).is_ok());
+        }
+    }
+}
+
+/// A minimal tag/text/comment/doctype scanner over `input`; the stack of
+/// currently-open elements is kept by its callers. This is not the HTML5 tokenization
+/// and tree construction state machines; it is just enough tag-soup
+/// handling to drive `Sink` for `parseFromString`.
+struct Tokenizer<'a> {
+    input: &'a str,
+    pos: uint,
+}
+
+enum Token {
+    StartTag(DOMString, Vec<(DOMString, DOMString)>, bool /* self-closing */),
+    EndTag(DOMString),
+    Comment(DOMString),
+    Doctype(DOMString),
+    Characters(DOMString),
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(input: &'a str) -> Tokenizer<'a> {
+        Tokenizer { input: input, pos: 0 }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        if self.pos >= self.input.len() {
+            return None;
+        }
+
+        if self.input.slice_from(self.pos).starts_with("<!--") {
+            let start = self.pos + 4;
+            let end = self.input.slice_from(start).find_str("-->")
+                                 .map(|i| start + i).unwrap_or(self.input.len());
+            let text = self.input.slice(start, end).to_string();
+            self.pos = end + 3;
+            return Some(Comment(text));
+        }
+
+        if self.input.slice_from(self.pos).to_ascii_lower().starts_with("<!doctype") {
+            let end = self.input.slice_from(self.pos).find('>')
+                                 .map(|i| self.pos + i).unwrap_or(self.input.len());
+            let name = self.input.slice(self.pos + 9, end).trim().to_string();
+            self.pos = end + 1;
+            return Some(Doctype(name));
+        }
+
+        if self.input.char_at(self.pos) == '<' {
+            let end = self.input.slice_from(self.pos).find('>')
+                                 .map(|i| self.pos + i).unwrap_or(self.input.len());
+            let inner = self.input.slice(self.pos + 1, end);
+            self.pos = end + 1;
+
+            if inner.starts_with("/") {
+                return Some(EndTag(inner.slice_from(1).trim().to_string()));
+            }
+
+            let self_closing = inner.ends_with("/");
+            let inner = if self_closing { inner.slice_to(inner.len() - 1) } else { inner };
+            let mut parts = inner.split(' ').filter(|s| !s.is_empty());
+            let name = parts.next().unwrap_or("").to_string();
+            let mut attrs = vec!();
+            for part in parts {
+                match part.find('=') {
+                    Some(i) => {
+                        let value = part.slice_from(i + 1).trim_chars('"');
+                        attrs.push((part.slice_to(i).to_string(), value.to_string()));
+                    }
+                    None => attrs.push((part.to_string(), "".to_string())),
+                }
+            }
+            return Some(StartTag(name, attrs, self_closing));
+        }
+
+        let end = self.input.slice_from(self.pos).find('<')
+                             .map(|i| self.pos + i).unwrap_or(self.input.len());
+        let text = self.input.slice(self.pos, end).to_string();
+        self.pos = end;
+        Some(Characters(text))
+    }
+}
+
+// Tag names with no content model (and so no matching end tag) in HTML.
+fn is_void_element(name: &str) -> bool {
+    match name {
+        "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input" |
+        "link" | "meta" | "param" | "source" | "track" | "wbr" => true,
+        _ => false,
+    }
+}
+
+// The elements a <table> is actually allowed to parent directly. Anything
+// else found while a <table> is the current open element is misnested
+// markup and gets foster-parented instead (see `parse_html`'s `StartTag`
+// arm).
+fn is_table_structure_child(name: &str) -> bool {
+    match name {
+        "caption" | "colgroup" | "col" | "tbody" | "tfoot" | "thead" |
+        "tr" | "td" | "th" => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Tokenizer, StartTag, EndTag, Comment, Doctype, Characters,
+                is_void_element, is_table_structure_child};
+
+    #[test]
+    fn tokenizes_a_doctype_tag_and_attributes() {
+        let mut tokenizer = Tokenizer::new("<!DOCTYPE html><p class=\"a\" id=b>hi<!--note--></p>");
+
+        match tokenizer.next() {
+            Some(Doctype(name)) => assert_eq!(name.as_slice(), "html"),
+            _ => fail!("expected a Doctype token"),
+        }
+        match tokenizer.next() {
+            Some(StartTag(name, attrs, self_closing)) => {
+                assert_eq!(name.as_slice(), "p");
+                assert_eq!(attrs, vec!(("class".to_string(), "a".to_string()),
+                                        ("id".to_string(), "b".to_string())));
+                assert!(!self_closing);
+            }
+            _ => fail!("expected a StartTag token"),
+        }
+        match tokenizer.next() {
+            Some(Characters(text)) => assert_eq!(text.as_slice(), "hi"),
+            _ => fail!("expected a Characters token"),
+        }
+        match tokenizer.next() {
+            Some(Comment(text)) => assert_eq!(text.as_slice(), "note"),
+            _ => fail!("expected a Comment token"),
+        }
+        match tokenizer.next() {
+            Some(EndTag(name)) => assert_eq!(name.as_slice(), "p"),
+            _ => fail!("expected an EndTag token"),
+        }
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn tokenizes_a_self_closing_tag() {
+        let mut tokenizer = Tokenizer::new("<br/>");
+        match tokenizer.next() {
+            Some(StartTag(name, _, self_closing)) => {
+                assert_eq!(name.as_slice(), "br");
+                assert!(self_closing);
+            }
+            _ => fail!("expected a self-closing StartTag token"),
+        }
+    }
+
+    #[test]
+    fn knows_the_html_void_elements() {
+        assert!(is_void_element("br"));
+        assert!(is_void_element("input"));
+        assert!(!is_void_element("div"));
+        assert!(!is_void_element("p"));
+    }
+
+    #[test]
+    fn knows_which_elements_a_table_may_parent_directly() {
+        assert!(is_table_structure_child("tbody"));
+        assert!(is_table_structure_child("tr"));
+        assert!(!is_table_structure_child("div"));
+        assert!(!is_table_structure_child("p"));
+    }
+}
+
+// http://domparsing.spec.whatwg.org/#dom-domparser-parsefromstring, "text/html" branch.
+pub fn parse_html(document: &JSRef<Document>, input: DOMString) {
+    let roots = RootCollection::new();
+    let sink = Sink::new(document);
+
+    // The implicit html/head/body insertion CreateHTMLDocument relies on.
+    let (head, body) = create_html_document_skeleton(document);
+
+    // Tracks the open-elements stack alongside each entry's tag name, so an
+    // end tag can be matched against the element it's actually meant to
+    // close rather than whatever happens to be on top (see the `EndTag` arm
+    // below).
+    let mut stack: Vec<(DOMString, Root<Node>)> = vec!(("body".to_string(), body));
+
+    let mut tokenizer = Tokenizer::new(input.as_slice());
+    while let Some(token) = tokenizer.next() {
+        match token {
+            Doctype(_) => (), // already synthesized above
+            Comment(text) => {
+                let comment_node: Root<Node> = NodeCast::from_unrooted(sink.create_comment(text)).root(&roots);
+                let &(_, ref parent) = stack.last().unwrap();
+                sink.append(parent.deref().clone(), AppendNode(JS::from_rooted(&*comment_node)));
+            }
+            Characters(text) => {
+                if !text.as_slice().trim().is_empty() {
+                    let &(_, ref parent) = stack.last().unwrap();
+                    sink.append(parent.deref().clone(), AppendText(text));
+                }
+            }
+            StartTag(name, attrs, self_closing) => {
+                match name.as_slice() {
+                    "html" | "head" | "body" => continue,
+                    _ => (),
+                }
+                // `parent_is_table` powers the foster-parenting check below:
+                // content that isn't one of a table's own structural
+                // children gets inserted before the table instead of inside
+                // it, the way real tree builders handle misnested markup
+                // like `<table><div>oops</div></table>`.
+                let (parent, parent_is_table) = if name.as_slice() == "title" {
+                    (head.deref().clone(), false)
+                } else {
+                    let &(ref parent_name, ref parent_node) = stack.last().unwrap();
+                    (parent_node.deref().clone(), parent_name.as_slice() == "table")
+                };
+                // An invalid tag name (e.g. the empty string from a bare
+                // "<>") can't become an element; drop the token rather than
+                // crash the parser over it, the way a tag-soup parser must.
+                let elem = match sink.create_element(name.clone(), attrs) {
+                    Some(elem) => elem,
+                    None => continue,
+                };
+
+                if name.as_slice() == "template" {
+                    // http://html.spec.whatwg.org/multipage/#the-template-element:
+                    // a <template>'s children belong in its `.content`
+                    // fragment, not in the template element itself.
+                    let elem_root: Root<Element> = elem.root(&roots);
+                    let elem_node_ref: &JSRef<Node> = NodeCast::from_ref(&*elem_root);
+                    sink.append(parent, AppendNode(JS::from_rooted(elem_node_ref)));
+                    if !self_closing {
+                        let content: Root<Node> =
+                            sink.get_template_contents(elem_root.deref().clone()).root(&roots);
+                        stack.push((name, content));
+                    }
+                    continue;
+                }
+
+                let elem_node: Root<Node> = NodeCast::from_unrooted(elem).root(&roots);
+                if parent_is_table && !is_table_structure_child(name.as_slice()) {
+                    // http://dev.w3.org/html5/spec/tree-construction.html#foster-parent
+                    sink.append_before_sibling(parent, AppendNode(JS::from_rooted(&*elem_node)));
+                } else {
+                    sink.append(parent, AppendNode(JS::from_rooted(&*elem_node)));
+                }
+                if !self_closing && !is_void_element(name.as_slice()) {
+                    stack.push((name, elem_node));
+                }
+            }
+            EndTag(name) => {
+                match name.as_slice() {
+                    "html" | "head" | "body" => (),
+                    _ => {
+                        // "Any other end tag": walk up from the top looking
+                        // for an open element with this name, and if one is
+                        // found, pop everything above and including it.
+                        // Never pop the bottom `body` sentinel. A stray end
+                        // tag with no matching open element (e.g. the `</br>`
+                        // that follows a void `<br>`, which was never
+                        // pushed) is simply ignored, rather than popping
+                        // whatever unrelated element happens to be on top.
+                        let mut match_pos = None;
+                        for (pos, &(ref open_name, _)) in stack.iter().enumerate() {
+                            if open_name.as_slice() == name.as_slice() {
+                                match_pos = Some(pos);
+                            }
+                        }
+                        match match_pos {
+                            Some(pos) if pos > 0 => stack.truncate(pos),
+                            _ => (),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// http://domparsing.spec.whatwg.org/#dom-domparser-parsefromstring,
+// "application/xml"/"image/svg+xml" branch. Returns `Err(())` on the first
+// well-formedness violation (here: a start tag with no matching end tag, a
+// mismatched or unopened end tag, a second document element, or no document
+// element at all), at which point the caller renders a parsererror document
+// that keeps the partial tree built so far as its children.
+pub fn parse_xml(document: &JSRef<Document>, input: DOMString) -> Result<(), ()> {
+    let roots = RootCollection::new();
+    let sink = Sink::new(document);
+    let doc_node: &JSRef<Node> = NodeCast::from_ref(document);
+
+    let mut stack: Vec<(DOMString, Root<Node>)> = vec!(("".to_string(), doc_node.unrooted().root(&roots)));
+
+    // XML well-formedness requires exactly one document element. Detected
+    // with `same_node` (the way a real tree builder checks "is the
+    // insertion point the document itself") rather than a depth count, so
+    // it only fires for elements actually inserted as a child of the
+    // document, not merely top-level-looking markup.
+    let mut root_elements = 0u;
+
+    let mut tokenizer = Tokenizer::new(input.as_slice());
+    while let Some(token) = tokenizer.next() {
+        match token {
+            Doctype(name) => sink.append_doctype_to_document(name, "".to_string(), "".to_string()),
+            Comment(text) => {
+                let comment_node: Root<Node> = NodeCast::from_unrooted(sink.create_comment(text)).root(&roots);
+                let &(_, ref parent) = stack.last().unwrap();
+                sink.append(parent.deref().clone(), AppendNode(JS::from_rooted(&*comment_node)));
+            }
+            Characters(text) => {
+                let &(_, ref parent) = stack.last().unwrap();
+                sink.append(parent.deref().clone(), AppendText(text));
+            }
+            StartTag(name, attrs, self_closing) => {
+                // As in `parse_html`: an invalid tag name produces no
+                // element, and since it never goes on `stack`, its matching
+                // end tag (if any) will correctly be rejected below as
+                // unopened.
+                let elem = match sink.create_element(name.clone(), attrs) {
+                    Some(elem) => elem,
+                    None => continue,
+                };
+                let elem_node: Root<Node> = NodeCast::from_unrooted(elem).root(&roots);
+                {
+                    let &(_, ref parent) = stack.last().unwrap();
+                    if sink.same_node(parent.deref().clone(), doc_node.clone()) {
+                        root_elements += 1;
+                        if root_elements > 1 {
+                            return Err(()); // a second document element: not well-formed
+                        }
+                    }
+                    sink.append(parent.deref().clone(), AppendNode(JS::from_rooted(&*elem_node)));
+                }
+                if !self_closing {
+                    stack.push((name, elem_node));
+                }
+            }
+            EndTag(name) => {
+                match stack.pop() {
+                    Some((open_name, _)) if open_name == name => (),
+                    _ => return Err(()), // mismatched or unopened end tag: not well-formed
+                }
+            }
+        }
+    }
+
+    if stack.len() != 1 || root_elements == 0 {
+        return Err(()); // unclosed element(s), or no document element at all: not well-formed
+    }
+
+    Ok(())
+}
+
+/// http://domparsing.spec.whatwg.org/#dom-domparser-parsefromstring,
+/// final step of the XML branches: make a `parsererror` document element
+/// the document's sole child, carrying over whatever was already built as
+/// its own children instead of discarding it, the way real implementations
+/// keep the partial markup visible alongside the error for diagnosis.
+pub fn render_parser_error(document: &JSRef<Document>) {
+    let roots = RootCollection::new();
+    let doc_node: &JSRef<Node> = NodeCast::from_ref(document);
+
+    let sink = Sink::new(document);
+    // "parsererror" is always a valid qualified name, so this is not
+    // expected to fail in practice; `create_element` is fallible in
+    // general (arbitrary tag-soup input), so it's handled here too rather
+    // than asserted.
+    let error = match sink.create_element("parsererror".to_string(), vec!()) {
+        Some(error) => error,
+        None => return,
+    };
+    let mut error_node: Root<Node> = NodeCast::from_unrooted(error).root(&roots);
+
+    sink.reparent_children(doc_node.clone(), error_node.deref().clone());
+    assert!(doc_node.clone().AppendChild(&mut *error_node).is_ok());
+}
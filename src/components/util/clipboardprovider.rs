@@ -0,0 +1,117 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Abstracts the system clipboard behind a small trait so headless and test
+//! builds can substitute a string-backed stub instead of talking to a real
+//! platform clipboard.
+
+use std::io::Command;
+use std::str::from_utf8;
+
+/// Implemented by whatever owns the system clipboard for a top-level
+/// browsing context (a `Window`/`Document`, or a test harness).
+pub trait ClipboardProvider {
+    fn get_clipboard_contents(&mut self) -> String;
+    fn set_clipboard_contents(&mut self, String);
+}
+
+/// An in-memory `ClipboardProvider` for headless and test builds, where
+/// there is no real platform clipboard to read from or write to.
+pub struct DummyClipboardContext {
+    content: String,
+}
+
+impl DummyClipboardContext {
+    pub fn new(initial: &str) -> DummyClipboardContext {
+        DummyClipboardContext {
+            content: initial.to_string(),
+        }
+    }
+}
+
+impl ClipboardProvider for DummyClipboardContext {
+    fn get_clipboard_contents(&mut self) -> String {
+        self.content.clone()
+    }
+
+    fn set_clipboard_contents(&mut self, s: String) {
+        self.content = s;
+    }
+}
+
+/// A `ClipboardProvider` backed by the host platform's clipboard, shelling
+/// out to the command line tool each platform already ships with one
+/// (there's no clipboard crate in this build's dependency set). Falls back
+/// to an in-memory `DummyClipboardContext` when the platform tool isn't
+/// available, e.g. in a headless CI environment with no clipboard at all.
+pub struct SystemClipboardContext {
+    fallback: DummyClipboardContext,
+}
+
+impl SystemClipboardContext {
+    pub fn new() -> SystemClipboardContext {
+        SystemClipboardContext {
+            fallback: DummyClipboardContext::new(""),
+        }
+    }
+
+    fn paste_command() -> Command {
+        if cfg!(target_os = "macos") {
+            Command::new("pbpaste")
+        } else {
+            let mut command = Command::new("xclip");
+            command.args(&["-selection", "clipboard", "-o"]);
+            command
+        }
+    }
+
+    fn copy_command() -> Command {
+        if cfg!(target_os = "macos") {
+            Command::new("pbcopy")
+        } else {
+            let mut command = Command::new("xclip");
+            command.args(&["-selection", "clipboard"]);
+            command
+        }
+    }
+}
+
+impl ClipboardProvider for SystemClipboardContext {
+    fn get_clipboard_contents(&mut self) -> String {
+        match SystemClipboardContext::paste_command().output() {
+            Ok(output) => from_utf8(output.output.as_slice()).unwrap_or("").to_string(),
+            Err(_) => self.fallback.get_clipboard_contents(),
+        }
+    }
+
+    fn set_clipboard_contents(&mut self, s: String) {
+        self.fallback.set_clipboard_contents(s.clone());
+
+        let mut command = SystemClipboardContext::copy_command();
+        if let Ok(mut process) = command.spawn() {
+            if let Some(ref mut stdin) = process.stdin {
+                let _ = stdin.write_str(s.as_slice());
+            }
+            // `pbcopy`/`xclip` read until EOF on stdin before exiting; drop
+            // the write end now so the child actually sees that EOF,
+            // instead of `wait()` blocking on a child that's blocked on us.
+            drop(process.stdin.take());
+            let _ = process.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ClipboardProvider, DummyClipboardContext};
+
+    #[test]
+    fn roundtrips_through_get_and_set() {
+        let mut context = DummyClipboardContext::new("initial");
+        assert_eq!(context.get_clipboard_contents().as_slice(), "initial");
+
+        context.set_clipboard_contents("updated".to_string());
+        assert_eq!(context.get_clipboard_contents().as_slice(), "updated");
+    }
+}